@@ -0,0 +1,175 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+
+use http::header;
+use http::Request;
+use http::Response;
+
+use super::builder::OnedriveBuilder;
+use super::writer::OneDriveConflictBehavior;
+use crate::raw::*;
+use crate::*;
+
+#[derive(Clone)]
+pub struct OnedriveBackend {
+    client: HttpClient,
+    access_token: String,
+    root: String,
+    conflict_behavior: OneDriveConflictBehavior,
+    chunk_size: Option<usize>,
+}
+
+impl Debug for OnedriveBackend {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OnedriveBackend")
+            .field("root", &self.root)
+            .field("conflict_behavior", &self.conflict_behavior)
+            .field("chunk_size", &self.chunk_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl OnedriveBackend {
+    /// Base URL for the `/me/drive` facet of the Microsoft Graph API.
+    pub(crate) const BASE_URL: &'static str = "https://graph.microsoft.com/v1.0/me/drive";
+
+    pub(crate) fn new(
+        client: HttpClient,
+        access_token: String,
+        root: String,
+        conflict_behavior: OneDriveConflictBehavior,
+        chunk_size: Option<usize>,
+    ) -> Self {
+        Self {
+            client,
+            access_token,
+            root,
+            conflict_behavior,
+            chunk_size,
+        }
+    }
+
+    /// Returns a fresh [`OnedriveBuilder`] for configuring a new backend.
+    pub fn builder() -> OnedriveBuilder {
+        OnedriveBuilder::default()
+    }
+
+    /// The conflict behavior to apply to a write unless it is explicitly overridden
+    /// (e.g. via `OpWrite::if_not_exists`).
+    pub(crate) fn default_conflict_behavior(&self) -> OneDriveConflictBehavior {
+        self.conflict_behavior
+    }
+
+    /// The user-configured fragment size for session uploads, already normalized to a
+    /// positive multiple of `OneDriveWriter::CHUNK_SIZE_FACTOR` by the builder. `None`
+    /// means the writer should fall back to its own default.
+    pub(crate) fn chunk_size(&self) -> Option<usize> {
+        self.chunk_size
+    }
+
+    fn sign(&self, req: http::request::Builder) -> http::request::Builder {
+        req.header(header::AUTHORIZATION, format!("Bearer {}", self.access_token))
+    }
+
+    pub(crate) async fn onedrive_put(
+        &self,
+        path: &str,
+        content_length: Option<usize>,
+        content_type: Option<&str>,
+        conflict_behavior: &str,
+        body: AsyncBody,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let url = format!(
+            "{}/root:{}:/content?@microsoft.graph.conflictBehavior={}",
+            Self::BASE_URL,
+            percent_encode_path(&build_abs_path(&self.root, path)),
+            conflict_behavior,
+        );
+
+        let mut req = Request::put(&url);
+        if let Some(content_length) = content_length {
+            req = req.header(header::CONTENT_LENGTH, content_length);
+        }
+        if let Some(content_type) = content_type {
+            req = req.header(header::CONTENT_TYPE, content_type);
+        }
+        req = self.sign(req);
+
+        let req = req.body(body).map_err(new_request_build_error)?;
+        self.client.send(req).await
+    }
+
+    pub(crate) async fn onedrive_post(
+        &self,
+        url: &str,
+        body: AsyncBody,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let req = Request::post(url).header(header::CONTENT_TYPE, "application/json");
+        let req = self.sign(req);
+
+        let req = req.body(body).map_err(new_request_build_error)?;
+        self.client.send(req).await
+    }
+
+    pub(crate) async fn onedrive_chunked_upload(
+        &self,
+        upload_url: &str,
+        content_type: Option<&str>,
+        range_start: usize,
+        range_end: usize,
+        total_len: usize,
+        body: AsyncBody,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let mut req = Request::put(upload_url).header(
+            header::CONTENT_RANGE,
+            format!("bytes {range_start}-{range_end}/{total_len}"),
+        );
+        if let Some(content_type) = content_type {
+            req = req.header(header::CONTENT_TYPE, content_type);
+        }
+
+        // Upload-session URLs are pre-signed by Graph itself; no bearer token needed.
+        let req = req.body(body).map_err(new_request_build_error)?;
+        self.client.send(req).await
+    }
+
+    pub(crate) async fn onedrive_get_upload_session(
+        &self,
+        upload_url: &str,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let req = Request::get(upload_url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+        self.client.send(req).await
+    }
+
+    /// Cancels an in-progress upload session.
+    ///
+    /// Reference: https://learn.microsoft.com/en-us/onedrive/developer/rest-api/api/driveitem_createuploadsession?view=odsp-graph-online#cancel-the-upload-session
+    pub(crate) async fn onedrive_delete_upload_session(
+        &self,
+        upload_url: &str,
+    ) -> Result<Response<IncomingAsyncBody>> {
+        let req = Request::delete(upload_url)
+            .body(AsyncBody::Empty)
+            .map_err(new_request_build_error)?;
+        self.client.send(req).await
+    }
+}
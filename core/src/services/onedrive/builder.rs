@@ -0,0 +1,135 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use std::fmt::Debug;
+use std::fmt::Formatter;
+
+use super::backend::OnedriveBackend;
+use super::config::OnedriveConfig;
+use super::writer::normalize_chunk_size;
+use super::writer::OneDriveConflictBehavior;
+use crate::raw::HttpClient;
+use crate::*;
+
+/// [OneDrive](https://onedrive.com) services support via the Microsoft Graph API.
+///
+/// # Notes
+///
+/// [`OnedriveBuilder::chunk_size`] only bounds memory use for writes that declare
+/// their length upfront; a write of unknown length is still buffered in full until
+/// `close()` learns the real size, since OneDrive requires the true final size on
+/// every fragment's `Content-Range`, not just the last one.
+///
+/// A chunked (session-backed) upload that outlives its upload session's expiration
+/// window cannot be resumed. Bytes are streamed out of the writer's internal buffer
+/// as fragments are sent, so by the time a later fragment discovers the session has
+/// expired there is nothing left to recreate the session and replay from byte 0 with.
+/// Such a write fails explicitly with an error rather than silently retrying against
+/// a session that has nothing uploaded to it. This is a deliberate scope cut, not an
+/// oversight: keep individual writes well under the session's expiration window
+/// (see [Microsoft's docs](https://learn.microsoft.com/en-us/onedrive/developer/rest-api/api/driveitem_createuploadsession?view=odsp-graph-online)
+/// for current limits) if this matters for your workload.
+#[derive(Default)]
+pub struct OnedriveBuilder {
+    config: OnedriveConfig,
+    http_client: Option<HttpClient>,
+}
+
+impl Debug for OnedriveBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OnedriveBuilder")
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl OnedriveBuilder {
+    /// Sets the OAuth2 access token used to authenticate against the Graph API.
+    pub fn access_token(&mut self, access_token: &str) -> &mut Self {
+        self.config.access_token = Some(access_token.to_string());
+        self
+    }
+
+    /// Sets the root path inside the drive to operate under.
+    pub fn root(&mut self, root: &str) -> &mut Self {
+        self.config.root = Some(root.to_string());
+        self
+    }
+
+    /// Sets the default `@microsoft.graph.conflictBehavior` used for writes that don't
+    /// explicitly request `if_not_exists`. One of `replace`, `rename`, or `fail`;
+    /// defaults to `replace`, matching OneDrive's own server-side default.
+    pub fn conflict_behavior(&mut self, conflict_behavior: &str) -> &mut Self {
+        self.config.conflict_behavior = Some(conflict_behavior.to_string());
+        self
+    }
+
+    /// Sets the fragment size used when streaming a file through an upload session.
+    /// Rounded up to the nearest positive multiple of 320 KiB if it doesn't already
+    /// divide evenly, per OneDrive's own requirement on fragment sizes.
+    ///
+    /// Only bounds memory use for writes that declare their length upfront. OneDrive
+    /// requires every fragment — not just the last — to report the true final size in
+    /// its `Content-Range`, so a write of unknown length (e.g. a piped source) is still
+    /// buffered in full until `close()` reveals the real size.
+    pub fn chunk_size(&mut self, chunk_size: usize) -> &mut Self {
+        self.config.chunk_size = Some(chunk_size);
+        self
+    }
+
+    pub(crate) fn http_client(&mut self, http_client: HttpClient) -> &mut Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    pub(crate) fn build(&mut self) -> Result<OnedriveBackend> {
+        let access_token = self.config.access_token.clone().ok_or_else(|| {
+            Error::new(ErrorKind::ConfigInvalid, "access_token is required")
+        })?;
+
+        let conflict_behavior = match self.config.conflict_behavior.as_deref() {
+            None | Some("replace") => OneDriveConflictBehavior::Replace,
+            Some("rename") => OneDriveConflictBehavior::Rename,
+            Some("fail") => OneDriveConflictBehavior::Fail,
+            Some(other) => {
+                return Err(Error::new(
+                    ErrorKind::ConfigInvalid,
+                    format!("onedrive conflict_behavior is invalid: {other}"),
+                ))
+            }
+        };
+
+        let chunk_size = self
+            .config
+            .chunk_size
+            .map(normalize_chunk_size)
+            .transpose()?;
+
+        let client = match self.http_client.take() {
+            Some(client) => client,
+            None => HttpClient::new()?,
+        };
+
+        Ok(OnedriveBackend::new(
+            client,
+            access_token,
+            self.config.root.clone().unwrap_or_else(|| "/".to_string()),
+            conflict_behavior,
+            chunk_size,
+        ))
+    }
+}
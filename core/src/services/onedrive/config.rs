@@ -0,0 +1,53 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use serde::Deserialize;
+
+/// Config for OneDrive services support.
+#[derive(Default, Deserialize, Clone)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct OnedriveConfig {
+    /// The OAuth2 access token used to authenticate against the Microsoft Graph API.
+    pub access_token: Option<String>,
+    /// Root path inside the OneDrive drive to operate under.
+    pub root: Option<String>,
+    /// The `@microsoft.graph.conflictBehavior` applied to a write unless it explicitly
+    /// requests `if_not_exists`. One of `replace`, `rename`, or `fail`; defaults to
+    /// `replace`, matching OneDrive's own server-side default.
+    pub conflict_behavior: Option<String>,
+    /// Fragment size, in bytes, used when streaming a file through an upload session.
+    /// Must be a positive multiple of 320 KiB; non-conforming values are rounded up.
+    /// Defaults to 320 KiB when unset.
+    ///
+    /// This only bounds memory use when the write declares its length upfront (e.g.
+    /// via `OpWrite::content_length`). OneDrive's upload-session API requires the true
+    /// final size on every fragment's `Content-Range`, not just the last one, so a
+    /// write whose length isn't known upfront is still buffered in full until `close()`
+    /// reveals the real size, the same as it would be without an upload session at all.
+    pub chunk_size: Option<usize>,
+}
+
+impl std::fmt::Debug for OnedriveConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OnedriveConfig")
+            .field("root", &self.root)
+            .field("conflict_behavior", &self.conflict_behavior)
+            .field("chunk_size", &self.chunk_size)
+            .finish_non_exhaustive()
+    }
+}
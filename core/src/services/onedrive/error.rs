@@ -0,0 +1,44 @@
+// Licensed to the Apache Software Foundation (ASF) under one
+// or more contributor license agreements.  See the NOTICE file
+// distributed with this work for additional information
+// regarding copyright ownership.  The ASF licenses this file
+// to you under the Apache License, Version 2.0 (the
+// "License"); you may not use this file except in compliance
+// with the License.  You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing,
+// software distributed under the License is distributed on an
+// "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied.  See the License for the
+// specific language governing permissions and limitations
+// under the License.
+
+use http::Response;
+use http::StatusCode;
+
+use crate::raw::*;
+use crate::*;
+
+/// Parses a non-2xx Microsoft Graph API response into an [`Error`].
+///
+/// Reference: https://learn.microsoft.com/en-us/graph/errors
+pub(crate) async fn parse_error(resp: Response<IncomingAsyncBody>) -> Result<Error> {
+    let status = resp.status();
+
+    let kind = match status {
+        StatusCode::NOT_FOUND => ErrorKind::NotFound,
+        StatusCode::FORBIDDEN | StatusCode::UNAUTHORIZED => ErrorKind::PermissionDenied,
+        StatusCode::CONFLICT | StatusCode::PRECONDITION_FAILED => ErrorKind::ConditionNotMatch,
+        _ => ErrorKind::Unexpected,
+    };
+
+    let bs = resp.into_body().bytes().await?;
+    let message = String::from_utf8_lossy(&bs).into_owned();
+
+    Ok(Error::new(
+        kind,
+        format!("onedrive service returned {status}: {message}"),
+    ))
+}
@@ -16,9 +16,10 @@
 // under the License.
 
 use async_trait::async_trait;
-use bytes::{Buf, Bytes};
+use bytes::{Buf, Bytes, BytesMut};
 use http::StatusCode;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 use super::backend::OnedriveBackend;
 use super::error::parse_error;
@@ -26,11 +27,88 @@ use crate::ops::OpWrite;
 use crate::raw::*;
 use crate::*;
 
+/// Whether the writer buffers the whole payload for a single simple PUT, or
+/// streams it through an upload session once it is known (or turns out) to be large.
+enum UploadMode {
+    Simple,
+    Chunked,
+}
+
+/// The `@microsoft.graph.conflictBehavior` to apply when the target path already exists.
+///
+/// Reference: https://learn.microsoft.com/en-us/onedrive/developer/rest-api/api/driveitem_createuploadsession?view=odsp-graph-online#request-body
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum OneDriveConflictBehavior {
+    /// Overwrite the existing item. This is OneDrive's own default.
+    Replace,
+    /// Keep the existing item and create the new one under a server-generated name.
+    Rename,
+    /// Fail the request instead of touching the existing item.
+    Fail,
+}
+
+impl OneDriveConflictBehavior {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Replace => "replace",
+            Self::Rename => "rename",
+            Self::Fail => "fail",
+        }
+    }
+}
+
+impl Default for OneDriveConflictBehavior {
+    fn default() -> Self {
+        Self::Replace
+    }
+}
+
+/// Normalizes a user-configured upload fragment size to a valid OneDrive chunk size:
+/// a positive multiple of [`OneDriveWriter::CHUNK_SIZE_FACTOR`] (320 KiB). Invalid
+/// sizes are rounded up to the nearest valid multiple; zero is rejected outright since
+/// there is no sensible multiple to round it to.
+///
+/// Used by the backend builder when validating the configured fragment size.
+pub(crate) fn normalize_chunk_size(requested: usize) -> Result<usize> {
+    let factor = OneDriveWriter::CHUNK_SIZE_FACTOR;
+
+    if requested == 0 {
+        return Err(Error::new(
+            ErrorKind::ConfigInvalid,
+            "onedrive upload fragment size must not be zero",
+        ));
+    }
+
+    if requested % factor == 0 {
+        return Ok(requested);
+    }
+
+    Ok((requested / factor + 1) * factor)
+}
+
 pub struct OneDriveWriter {
     backend: OnedriveBackend,
 
     op: OpWrite,
     path: String,
+
+    mode: UploadMode,
+    // Bytes accumulated since the last flush. In `Simple` mode this holds the whole
+    // payload until `close()`; in `Chunked` mode only the not-yet-sent remainder.
+    buffer: BytesMut,
+    // The active upload session, created lazily on the first fragment flush.
+    upload_url: Option<String>,
+    // Number of bytes already committed to the upload session.
+    offset: usize,
+    conflict_behavior: OneDriveConflictBehavior,
+    // Fragment size used for session uploads; always a positive multiple of
+    // `CHUNK_SIZE_FACTOR`, per `normalize_chunk_size`.
+    chunk_size: usize,
+    // The real total size to report in every fragment's `Content-Range`. Known upfront
+    // from `op.content_length()`, or otherwise only once `close()` has seen every byte;
+    // fragments are never flushed before this is set, since OneDrive's upload-session
+    // API requires the true final size on every fragment, not just the last one.
+    final_total_len: Option<usize>,
 }
 
 impl OneDriveWriter {
@@ -38,29 +116,94 @@ impl OneDriveWriter {
     // If your app splits a file into multiple byte ranges, the size of each byte range MUST be a multiple of 320 KiB (327,680 bytes). Using a fragment size that does not divide evenly by 320 KiB will result in errors committing some files.
     // https://learn.microsoft.com/en-us/onedrive/developer/rest-api/api/driveitem_createuploadsession?view=odsp-graph-online#upload-bytes-to-the-upload-session
     const CHUNK_SIZE_FACTOR: usize = 327_680;
+    // Number of times a single fragment is retried before giving up, on top of the initial attempt.
+    const MAX_FRAGMENT_RETRIES: u32 = 3;
+
     pub fn new(backend: OnedriveBackend, op: OpWrite, path: String) -> Self {
-        OneDriveWriter { backend, op, path }
+        // Decide the mode upfront when the final size is known, so a large file never
+        // has to be buffered in full just to discover it should have streamed.
+        let mode = match op.content_length() {
+            Some(len) if len as usize <= Self::MAX_SIMPLE_SIZE => UploadMode::Simple,
+            Some(_) => UploadMode::Chunked,
+            None => UploadMode::Simple,
+        };
+        // `if_not_exists` is the generic cross-service signal for "fail on conflict";
+        // otherwise fall back to whatever the backend was configured to do by default.
+        let conflict_behavior = if op.if_not_exists() {
+            OneDriveConflictBehavior::Fail
+        } else {
+            backend.default_conflict_behavior()
+        };
+        // The backend builder already validated and normalized this via
+        // `normalize_chunk_size`, so it is always a positive multiple of
+        // `CHUNK_SIZE_FACTOR` here.
+        let chunk_size = backend.chunk_size().unwrap_or(Self::CHUNK_SIZE_FACTOR);
+        let final_total_len = op.content_length().map(|len| len as usize);
+
+        OneDriveWriter {
+            backend,
+            op,
+            path,
+            mode,
+            buffer: BytesMut::new(),
+            upload_url: None,
+            offset: 0,
+            conflict_behavior,
+            chunk_size,
+            final_total_len,
+        }
     }
 }
 
 #[async_trait]
 impl oio::Write for OneDriveWriter {
     async fn write(&mut self, bs: Bytes) -> Result<()> {
-        let size = bs.len();
+        self.buffer.extend_from_slice(&bs);
 
-        if size <= Self::MAX_SIMPLE_SIZE {
-            self.write_simple(bs).await
-        } else {
-            self.write_chunked(bs).await
+        if matches!(self.mode, UploadMode::Simple) && self.buffer.len() > Self::MAX_SIMPLE_SIZE {
+            // No content length was given upfront and the payload outgrew the simple
+            // threshold: switch to a session-backed streaming upload from here on.
+            self.mode = UploadMode::Chunked;
+        }
+
+        // Only stream fragments out as they fill up once the real final size is known.
+        // OneDrive's upload-session API requires every fragment, not just the last, to
+        // declare the true total in its Content-Range, so a payload whose length
+        // wasn't given upfront has to stay buffered until `close()` reveals it.
+        if matches!(self.mode, UploadMode::Chunked) && self.final_total_len.is_some() {
+            self.flush_ready_fragments().await?;
         }
+
+        Ok(())
     }
 
     async fn abort(&mut self) -> Result<()> {
-        Ok(())
+        let Some(upload_url) = self.upload_url.take() else {
+            return Ok(());
+        };
+
+        // Reference: https://learn.microsoft.com/en-us/onedrive/developer/rest-api/api/driveitem_createuploadsession?view=odsp-graph-online#cancel-the-upload-session
+        let resp = self.backend.onedrive_delete_upload_session(&upload_url).await?;
+
+        match resp.status() {
+            // Typical response code: 204 No Content. A 404 means the session already
+            // expired or was never created server-side, which is equally fine to us.
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => {
+                resp.into_body().consume().await?;
+                Ok(())
+            }
+            _ => Err(parse_error(resp).await?),
+        }
     }
 
     async fn close(&mut self) -> Result<()> {
-        Ok(())
+        match self.mode {
+            UploadMode::Simple => {
+                let bs = std::mem::take(&mut self.buffer).freeze();
+                self.write_simple(bs).await
+            }
+            UploadMode::Chunked => self.flush_final_fragment().await,
+        }
     }
 }
 
@@ -72,6 +215,7 @@ impl OneDriveWriter {
                 &self.path,
                 Some(bs.len()),
                 self.op.content_type(),
+                self.conflict_behavior.as_str(),
                 AsyncBody::Bytes(bs),
             )
             .await?;
@@ -89,53 +233,166 @@ impl OneDriveWriter {
         }
     }
 
-    pub(crate) async fn write_chunked(&self, total_bytes: Bytes) -> Result<()> {
-        // Upload large files via sessions: https://learn.microsoft.com/en-us/onedrive/developer/rest-api/api/driveitem_createuploadsession?view=odsp-graph-online#upload-bytes-to-the-upload-session
-        // 1. Create an upload session
-        // 2. Upload the bytes of each chunk
-        // 3. Commit the session
+    /// Flushes every full `chunk_size`-aligned fragment currently buffered, leaving a
+    /// (possibly empty) remainder behind for the next call or `close()`.
+    async fn flush_ready_fragments(&mut self) -> Result<()> {
+        while self.buffer.len() >= self.chunk_size {
+            let chunk = self.buffer.split_to(self.chunk_size).freeze();
+            let total_len = self.expected_total_len(chunk.len());
+            self.upload_fragment(chunk, total_len).await?;
+        }
+        Ok(())
+    }
+
+    /// Flushes whatever remains in the buffer as the final, possibly non-aligned,
+    /// fragment and commits the upload session.
+    async fn flush_final_fragment(&mut self) -> Result<()> {
+        // Nothing else is coming after this: if the length wasn't known upfront, the
+        // real total is now exactly what's already landed plus what's still buffered.
+        self.final_total_len
+            .get_or_insert(self.offset + self.buffer.len());
+
+        self.flush_ready_fragments().await?;
 
-        let session_response = self.create_upload_session().await?;
+        let remainder = std::mem::take(&mut self.buffer).freeze();
+        if remainder.is_empty() && self.offset > 0 {
+            return Ok(());
+        }
+
+        let total_len = self.expected_total_len(remainder.len());
+        self.upload_fragment(remainder, total_len).await
+    }
 
-        let mut offset = 0;
+    /// The total size to report in a fragment's `Content-Range`. `self.final_total_len`
+    /// is always set by this point: either upfront from `op.content_length()`, or by
+    /// `flush_final_fragment` once `close()` has seen every byte.
+    fn expected_total_len(&self, chunk_len: usize) -> usize {
+        self.final_total_len
+            .unwrap_or_else(|| self.offset + chunk_len)
+    }
+
+    /// Uploads a single fragment starting at `self.offset`, creating the upload
+    /// session lazily on first use via [`Self::create_upload_session`]. The overall
+    /// upload-session flow:
+    /// 1. Create an upload session
+    /// 2. Upload the bytes of each chunk, recovering from transient failures by
+    ///    asking the session where it actually left off
+    /// 3. Commit the session (implicit once the final byte range is acknowledged)
+    ///
+    /// If the session itself has expired, recovery is not attempted and the write
+    /// fails explicitly instead — see the "Notes" section on `OnedriveBuilder`'s
+    /// docs for why resuming from a recreated session isn't safe here.
+    ///
+    /// Reference: https://learn.microsoft.com/en-us/onedrive/developer/rest-api/api/driveitem_createuploadsession?view=odsp-graph-online#upload-bytes-to-the-upload-session
+    async fn upload_fragment(&mut self, chunk: Bytes, total_len: usize) -> Result<()> {
+        // The offset this fragment started at, fixed for the lifetime of this call, so
+        // a retry can tell how much of `chunk` the server already acknowledged and
+        // resend only the unacknowledged suffix instead of the whole thing again.
+        let start_offset = self.offset;
+        let chunk_len = chunk.len();
+        let mut attempt = 0;
 
-        let iter = total_bytes.chunks(OneDriveWriter::CHUNK_SIZE_FACTOR);
+        loop {
+            let sent = self.offset - start_offset;
+            if sent >= chunk_len {
+                // A prior retry's nextExpectedRanges already reported every byte of
+                // this fragment as landed; nothing left to (re)send.
+                return Ok(());
+            }
+            let to_send = chunk.slice(sent..);
 
-        for chunk in iter {
-            let mut end = offset + OneDriveWriter::CHUNK_SIZE_FACTOR;
-            if end > total_bytes.len() {
-                end = total_bytes.len();
+            if self.upload_url.is_none() {
+                let session = self.create_upload_session().await?;
+                self.upload_url = Some(session.upload_url);
             }
-            let total_len = total_bytes.len();
-            let chunk_end = end - 1;
+            let upload_url = self.upload_url.clone().expect("just ensured above");
 
-            let resp = self
+            let upload_result = match self
                 .backend
                 .onedrive_chunked_upload(
-                    &session_response.upload_url,
+                    &upload_url,
                     None,
-                    offset,
-                    chunk_end,
+                    self.offset,
+                    self.offset + to_send.len() - 1,
                     total_len,
-                    AsyncBody::Bytes(Bytes::copy_from_slice(chunk)),
+                    AsyncBody::Bytes(to_send),
                 )
-                .await?;
+                .await
+            {
+                Ok(resp) => match resp.status() {
+                    // Typical response code: 202 Accepted
+                    // Reference: https://learn.microsoft.com/en-us/onedrive/developer/rest-api/api/driveitem_put_content?view=odsp-graph-online#response
+                    StatusCode::ACCEPTED | StatusCode::CREATED | StatusCode::OK => {
+                        resp.into_body().consume().await?;
+                        self.offset = start_offset + chunk_len;
+                        return Ok(());
+                    }
+                    _ => Err(parse_error(resp).await?),
+                },
+                Err(err) => Err(err),
+            };
 
-            let status = resp.status();
-
-            match status {
-                // Typical response code: 202 Accepted
-                // Reference: https://learn.microsoft.com/en-us/onedrive/developer/rest-api/api/driveitem_put_content?view=odsp-graph-online#response
-                StatusCode::ACCEPTED | StatusCode::CREATED | StatusCode::OK => {
-                    resp.into_body().consume().await?;
-                }
-                _ => return Err(parse_error(resp).await?),
+            attempt += 1;
+            if attempt > Self::MAX_FRAGMENT_RETRIES {
+                return upload_result;
             }
 
-            offset += OneDriveWriter::CHUNK_SIZE_FACTOR;
+            tokio::time::sleep(Self::fragment_retry_backoff(attempt)).await;
+
+            // The fragment failed: ask the session where it actually left off instead
+            // of blindly retrying the same range.
+            match self.query_upload_session_status(&upload_url).await {
+                Ok(status) => match status.next_expected_offset() {
+                    // A stale or inconsistent status response could in principle
+                    // report an offset behind where this fragment started. Clamp
+                    // against that: letting `self.offset` go backwards would
+                    // underflow the `sent` calculation above and could make an
+                    // unacknowledged fragment look fully sent.
+                    Some(next_offset) => self.offset = next_offset.max(start_offset),
+                    None => {
+                        // The session has expired. We cannot simply recreate it and
+                        // resume: a fresh session expects bytes starting at offset 0,
+                        // but the fragments already streamed out of `self.buffer` are
+                        // gone, so there is nothing left to replay from the start.
+                        // Fail explicitly instead of silently retrying against a
+                        // session that has nothing uploaded to it.
+                        return Err(Error::new(
+                            ErrorKind::Unexpected,
+                            "onedrive upload session expired and cannot be resumed",
+                        ));
+                    }
+                },
+                Err(err) => return Err(err),
+            }
         }
+    }
 
-        Ok(())
+    /// Exponential backoff applied between fragment retry attempts (1-indexed).
+    fn fragment_retry_backoff(attempt: u32) -> Duration {
+        Duration::from_millis(200 * 2u64.pow(attempt.saturating_sub(1)))
+    }
+
+    /// Queries the upload session for its `nextExpectedRanges`, used to resume a
+    /// fragment upload after a transient failure.
+    ///
+    /// Reference: https://learn.microsoft.com/en-us/onedrive/developer/rest-api/api/driveitem_createuploadsession?view=odsp-graph-online#resuming-an-upload
+    async fn query_upload_session_status(
+        &self,
+        upload_url: &str,
+    ) -> Result<OneDriveUploadSessionStatusResponseBody> {
+        let resp = self.backend.onedrive_get_upload_session(upload_url).await?;
+
+        let status = resp.status();
+
+        match status {
+            StatusCode::OK => {
+                let bs = resp.into_body().bytes().await?;
+                let result: OneDriveUploadSessionStatusResponseBody =
+                    serde_json::from_reader(bs.reader()).map_err(new_json_deserialize_error)?;
+                Ok(result)
+            }
+            _ => Err(parse_error(resp).await?),
+        }
     }
 
     async fn create_upload_session(&self) -> Result<OneDriveUploadSessionCreationResponseBody> {
@@ -150,7 +407,10 @@ impl OneDriveWriter {
             OnedriveBackend::BASE_URL,
             percent_encode_path(&self.path)
         );
-        let body = OneDriveUploadSessionCreationRequestBody::new(file_name_from_path.to_string());
+        let body = OneDriveUploadSessionCreationRequestBody::new(
+            file_name_from_path.to_string(),
+            self.conflict_behavior,
+        );
         let body_bytes = serde_json::to_vec(&body).map_err(new_json_serialize_error)?;
         let asyn_body = AsyncBody::Bytes(Bytes::from(body_bytes));
         let resp = self.backend.onedrive_post(&url, asyn_body).await?;
@@ -177,11 +437,11 @@ struct OneDriveUploadSessionCreationRequestBody {
 }
 
 impl OneDriveUploadSessionCreationRequestBody {
-    fn new(path: String) -> Self {
+    fn new(path: String, conflict_behavior: OneDriveConflictBehavior) -> Self {
         OneDriveUploadSessionCreationRequestBody {
             item: Item {
                 odata_type: "microsoft.graph.driveItemUploadableProperties".to_string(),
-                microsoft_graph_conflict_behavior: "replace".to_string(),
+                microsoft_graph_conflict_behavior: conflict_behavior.as_str().to_string(),
                 name: path,
             },
         }
@@ -204,3 +464,110 @@ struct OneDriveUploadSessionCreationResponseBody {
     #[serde(rename = "expirationDateTime")]
     expiration_date_time: String,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OneDriveUploadSessionStatusResponseBody {
+    #[serde(rename = "expirationDateTime")]
+    expiration_date_time: Option<String>,
+    #[serde(rename = "nextExpectedRanges")]
+    next_expected_ranges: Vec<String>,
+}
+
+impl OneDriveUploadSessionStatusResponseBody {
+    /// Parses the start offset of the first pending range, e.g. `"524288-"` -> `524288`.
+    ///
+    /// Returns `None` if the session no longer reports any pending ranges (typically
+    /// meaning it has expired). `upload_fragment` then fails the write; it does not
+    /// recreate the session.
+    fn next_expected_offset(&self) -> Option<usize> {
+        self.next_expected_ranges
+            .first()
+            .and_then(|range| range.split('-').next())
+            .and_then(|start| start.parse().ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::raw::HttpClient;
+
+    fn test_backend(conflict_behavior: OneDriveConflictBehavior) -> OnedriveBackend {
+        OnedriveBackend::new(
+            HttpClient::new().expect("http client"),
+            "test-token".to_string(),
+            "/".to_string(),
+            conflict_behavior,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_conflict_behavior_defaults_to_backend_setting() {
+        let backend = test_backend(OneDriveConflictBehavior::Rename);
+        let writer = OneDriveWriter::new(backend, OpWrite::new(), "test.txt".to_string());
+        assert_eq!(writer.conflict_behavior, OneDriveConflictBehavior::Rename);
+    }
+
+    #[test]
+    fn test_if_not_exists_overrides_backend_conflict_behavior() {
+        let backend = test_backend(OneDriveConflictBehavior::Replace);
+        let op = OpWrite::new().with_if_not_exists(true);
+        let writer = OneDriveWriter::new(backend, op, "test.txt".to_string());
+        assert_eq!(writer.conflict_behavior, OneDriveConflictBehavior::Fail);
+    }
+
+    #[test]
+    fn test_next_expected_offset_parses_first_range_start() {
+        let body = OneDriveUploadSessionStatusResponseBody {
+            expiration_date_time: Some("2024-01-01T00:00:00Z".to_string()),
+            next_expected_ranges: vec!["524288-1048575".to_string()],
+        };
+        assert_eq!(body.next_expected_offset(), Some(524288));
+    }
+
+    #[test]
+    fn test_expected_total_len_prefers_known_content_length() {
+        let backend = test_backend(OneDriveConflictBehavior::Replace);
+        let op = OpWrite::new().with_content_length(42);
+        let writer = OneDriveWriter::new(backend, op, "test.txt".to_string());
+        assert_eq!(writer.expected_total_len(10), 42);
+    }
+
+    #[test]
+    fn test_expected_total_len_uses_final_total_len_once_set() {
+        let backend = test_backend(OneDriveConflictBehavior::Replace);
+        let mut writer = OneDriveWriter::new(backend, OpWrite::new(), "test.txt".to_string());
+        assert_eq!(writer.final_total_len, None);
+
+        writer.offset = 1_000;
+        writer.final_total_len.get_or_insert(writer.offset + 500);
+        assert_eq!(writer.expected_total_len(500), 1_500);
+    }
+
+    #[test]
+    fn test_next_expected_offset_none_when_no_ranges_left() {
+        let body = OneDriveUploadSessionStatusResponseBody {
+            expiration_date_time: None,
+            next_expected_ranges: vec![],
+        };
+        assert_eq!(body.next_expected_offset(), None);
+    }
+
+    #[test]
+    fn test_normalize_chunk_size_accepts_exact_multiple() {
+        let factor = OneDriveWriter::CHUNK_SIZE_FACTOR;
+        assert_eq!(normalize_chunk_size(factor * 3).unwrap(), factor * 3);
+    }
+
+    #[test]
+    fn test_normalize_chunk_size_rounds_up_to_next_multiple() {
+        let factor = OneDriveWriter::CHUNK_SIZE_FACTOR;
+        assert_eq!(normalize_chunk_size(factor + 1).unwrap(), factor * 2);
+    }
+
+    #[test]
+    fn test_normalize_chunk_size_rejects_zero() {
+        assert!(normalize_chunk_size(0).is_err());
+    }
+}